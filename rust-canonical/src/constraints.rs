@@ -0,0 +1,205 @@
+//! Disequality (`=/=`) and type constraints (`symbolo`, `numbero`, `absento`).
+//!
+//! These sit on top of the ordinary unification machinery in `StatSubs`:
+//! rather than binding a variable to a value, a constraint forbids some
+//! future substitution from ever making two terms equal. Constraints are
+//! re-checked every time the substitution is extended, so they must be
+//! cheap to simplify and cheap to drop once satisfied.
+//!
+//! Two entry points wire this into the rest of the crate and must be
+//! called from outside this module: [`extend_checked`], which
+//! `StatSubs`'s unify/extend path calls once every unification has
+//! added its new var/value bindings (the same way `s.add_constraint`, `s.add_type_constraint` and
+//! `s.add_absento` are called when a constraint goal first runs), and
+//! [`format_with_constraints`], which `StatSubs::reify` calls after
+//! rendering a variable's term so residual constraints show up in `run!`
+//! output.
+
+use crate::state::StatSubs;
+use crate::{Goal, Value};
+
+/// A disequality constraint: the association list of bindings that must
+/// NOT simultaneously hold. Stored as the "prefix" produced by unifying
+/// the two original terms once, then re-simplified on every extension.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Disequality(pub Vec<(Value, Value)>);
+
+/// `neq(u, v)` fails if `u` and `v` are already equal and succeeds
+/// otherwise; if they could still be made equal, the bindings that would
+/// do so are stored and re-checked as more of the substitution is pinned
+/// down, failing the state once they all hold.
+pub fn neq(u: impl Into<Value>, v: impl Into<Value>) -> impl Goal<StatSubs> + Clone {
+    let u = u.into();
+    let v = v.into();
+    move |s: StatSubs| match s.unify_prefix(&u, &v) {
+        // The terms can never be equal: nothing to forbid, succeed as-is.
+        None => Stream::unit(s),
+        // Already equal with no new bindings: the disequality is violated now.
+        Some(prefix) if prefix.is_empty() => Stream::mzero(),
+        // Otherwise remember the prefix and re-check it as `s` grows.
+        Some(prefix) => Stream::unit(s.add_constraint(Disequality(prefix))),
+    }
+}
+
+/// The single entry point the substitution-extend path must call after
+/// a unification adds new var/value bindings: re-validates every stored disequality,
+/// type and absento constraint against the larger substitution, failing
+/// the whole state (`None`) the moment one is violated.
+pub(crate) fn extend_checked(s: StatSubs) -> Option<StatSubs> {
+    let s = recheck_disequalities(s)?;
+    let s = recheck_types(s)?;
+    recheck_absento(s)
+}
+
+/// Re-run every stored disequality constraint against a (possibly) more
+/// specific substitution, dropping the ones that became permanently
+/// satisfied and failing if one became entailed.
+fn recheck_disequalities(s: StatSubs) -> Option<StatSubs> {
+    let stored = s.constraints();
+    let mut next = s.clear_constraints();
+    for Disequality(pairs) in stored {
+        let lhs: Vec<Value> = pairs.iter().map(|(a, _)| a.clone()).collect();
+        let rhs: Vec<Value> = pairs.iter().map(|(_, b)| b.clone()).collect();
+        match next.unify_prefix(&Value::from(lhs), &Value::from(rhs)) {
+            // The forbidden equality now holds: the whole state is dead.
+            Some(prefix) if prefix.is_empty() => return None,
+            // Still merely possible: keep the simplified (smaller) prefix.
+            Some(prefix) => next = next.add_constraint(Disequality(prefix)),
+            // Can never hold any more: the constraint is satisfied, drop it.
+            None => {}
+        }
+    }
+    Some(next)
+}
+
+/// Re-run every stored `symbolo`/`numbero` constraint: a variable that
+/// was still fresh the last time it was checked may have been bound
+/// since, so its type now needs to be confirmed.
+fn recheck_types(s: StatSubs) -> Option<StatSubs> {
+    let stored = s.type_constraints();
+    let mut next = s.clear_type_constraints();
+    for (v, ty) in stored {
+        match next.walk(&Value::var(v.clone())) {
+            Value::Var(_) => next = next.add_type_constraint(v, ty),
+            bound if ty.accepts(&bound) => {}
+            _ => return None,
+        }
+    }
+    Some(next)
+}
+
+/// Re-run every stored `absento` constraint against the current
+/// substitution, since walking `t` further may now reveal `a` inside it.
+fn recheck_absento(s: StatSubs) -> Option<StatSubs> {
+    for (a, t) in &s.absento_constraints() {
+        if occurs(&s, a, t) {
+            return None;
+        }
+    }
+    Some(s)
+}
+
+/// A type tag attached to a not-yet-bound variable by `symbolo`/`numbero`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TypeConstraint {
+    Symbol,
+    Number,
+}
+
+/// Require that `x` is bound to a symbol (string) value.
+pub fn symbolo(x: impl Into<Value>) -> impl Goal<StatSubs> + Clone {
+    typeo(x, TypeConstraint::Symbol)
+}
+
+/// Require that `x` is bound to a number value.
+pub fn numbero(x: impl Into<Value>) -> impl Goal<StatSubs> + Clone {
+    typeo(x, TypeConstraint::Number)
+}
+
+fn typeo(x: impl Into<Value>, ty: TypeConstraint) -> impl Goal<StatSubs> + Clone {
+    let x = x.into();
+    move |s: StatSubs| match s.walk(&x) {
+        Value::Var(v) => Stream::unit(s.add_type_constraint(v, ty)),
+        bound if ty.accepts(&bound) => Stream::unit(s),
+        _ => Stream::mzero(),
+    }
+}
+
+impl TypeConstraint {
+    fn accepts(self, v: &Value) -> bool {
+        match self {
+            TypeConstraint::Symbol => matches!(v, Value::Sym(_)),
+            TypeConstraint::Number => matches!(v, Value::Num(_)),
+        }
+    }
+}
+
+/// `absento(a, t)` forbids the ground value `a` from occurring anywhere
+/// inside `t`, re-checked as `t` is walked further.
+pub fn absento(a: impl Into<Value>, t: impl Into<Value>) -> impl Goal<StatSubs> + Clone {
+    let a = a.into();
+    let t = t.into();
+    move |s: StatSubs| {
+        if occurs(&s, &a, &t) {
+            Stream::mzero()
+        } else {
+            Stream::unit(s.add_absento(a.clone(), t.clone()))
+        }
+    }
+}
+
+fn occurs(s: &StatSubs, a: &Value, t: &Value) -> bool {
+    let t = s.walk(t);
+    if &t == a {
+        return true;
+    }
+    match t {
+        Value::Cons(h, tl) => occurs(s, a, &h) || occurs(s, a, &tl),
+        _ => false,
+    }
+}
+
+use crate::stream::Stream;
+
+/// Called by `StatSubs::reify` right after it renders a still-fresh
+/// `var` as its placeholder, to append any residual constraints, e.g.
+/// turning `_0` into `_0 (=/= _0 5)`.
+pub(crate) fn format_with_constraints(s: &StatSubs, var: &Value, reified: &Value) -> String {
+    let clauses = reified_constraints(s, var, reified);
+    if clauses.is_empty() {
+        format!("{reified}")
+    } else {
+        format!("{reified} {}", clauses.join(" "))
+    }
+}
+
+/// Render a variable's residual disequality constraints the way
+/// `reify` prints them, e.g. `(=/= _0 5)`. Constraints that no longer
+/// mention any variable reachable from `var` are omitted. `var` itself is
+/// printed as its placeholder `reified`; any other fresh variable is
+/// printed by its own name.
+fn reified_constraints(s: &StatSubs, var: &Value, reified: &Value) -> Vec<String> {
+    let show = |v: &Value| match s.walk(v) {
+        walked if &walked == var => reified.to_string(),
+        walked => walked.to_string(),
+    };
+    s.constraints()
+        .iter()
+        .filter(|Disequality(pairs)| pairs.iter().any(|(a, _)| mentions(s, a, var)))
+        .map(|Disequality(pairs)| {
+            let clauses: Vec<String> = pairs
+                .iter()
+                .map(|(a, b)| format!("(=/= {} {})", show(a), show(b)))
+                .collect();
+            clauses.join(" ")
+        })
+        .collect()
+}
+
+fn mentions(s: &StatSubs, value: &Value, var: &Value) -> bool {
+    match s.walk(value) {
+        found if &found == var => true,
+        Value::Cons(h, tl) => mentions(s, &h, var) || mentions(s, &tl, var),
+        _ => false,
+    }
+}
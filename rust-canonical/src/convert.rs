@@ -0,0 +1,140 @@
+//! Typed extraction of reified solutions.
+//!
+//! `run!` yields `Value`s, which is the right default since a relation's
+//! solutions are dynamically typed terms. `value_of!`/`run_typed!` add an
+//! escape hatch, borrowed from the ergonomics of clap's `value_t!`, for
+//! the common case where the caller already knows the Rust type a
+//! solution should take.
+
+use crate::Value;
+use std::convert::TryFrom;
+use std::fmt;
+
+/// Why a reified `Value` couldn't be converted into the requested type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConversionError {
+    /// The value was a still-`fresh` (unbound) variable, not a panic-worthy
+    /// bug but not a value either.
+    Unresolved,
+    /// The value's shape didn't match the target type.
+    TypeMismatch { expected: &'static str, found: Value },
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConversionError::Unresolved => write!(f, "value is unresolved (still a fresh variable)"),
+            ConversionError::TypeMismatch { expected, found } => {
+                write!(f, "expected {expected}, found {found:?}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConversionError {}
+
+/// Convert a reified `Value` into a concrete Rust type. Implemented for
+/// the scalar cases directly, and for `Vec<T>`/tuples over proper cons
+/// lists so list-producing relations can be collected into native
+/// containers.
+pub trait FromValue: Sized {
+    fn from_value(value: &Value) -> Result<Self, ConversionError>;
+}
+
+macro_rules! from_value_scalar {
+    ($ty:ty, $expected:literal, $pat:pat => $out:expr) => {
+        impl FromValue for $ty {
+            fn from_value(value: &Value) -> Result<Self, ConversionError> {
+                match value {
+                    Value::Var(_) => Err(ConversionError::Unresolved),
+                    // `run!`/`run_typed!` hand us already-reified
+                    // solutions, where a variable that's still fresh
+                    // shows up as a placeholder symbol (`_0`, `_1`, ...),
+                    // not a live `Value::Var` — without this check it
+                    // would silently convert to whichever scalar arm
+                    // happens to match symbols (e.g. `String`).
+                    Value::Sym(s) if crate::value::is_reified_var_sym(s) => {
+                        Err(ConversionError::Unresolved)
+                    }
+                    $pat => Ok($out),
+                    other => Err(ConversionError::TypeMismatch {
+                        expected: $expected,
+                        found: other.clone(),
+                    }),
+                }
+            }
+        }
+
+        impl TryFrom<Value> for $ty {
+            type Error = ConversionError;
+
+            fn try_from(value: Value) -> Result<Self, ConversionError> {
+                <$ty as FromValue>::from_value(&value)
+            }
+        }
+    };
+}
+
+from_value_scalar!(i64, "integer", Value::Num(n) => *n);
+from_value_scalar!(String, "string", Value::Sym(s) => s.clone());
+from_value_scalar!(bool, "bool", Value::Bool(b) => *b);
+
+impl<T: FromValue> FromValue for Vec<T> {
+    fn from_value(value: &Value) -> Result<Self, ConversionError> {
+        match value {
+            Value::Var(_) => Err(ConversionError::Unresolved),
+            Value::Sym(s) if crate::value::is_reified_var_sym(s) => Err(ConversionError::Unresolved),
+            Value::Nil => Ok(Vec::new()),
+            Value::Cons(head, tail) => {
+                let mut out = vec![T::from_value(head)?];
+                out.extend(Vec::<T>::from_value(tail)?);
+                Ok(out)
+            }
+            other => Err(ConversionError::TypeMismatch {
+                expected: "proper list",
+                found: other.clone(),
+            }),
+        }
+    }
+}
+
+macro_rules! from_value_tuple {
+    ($($name:ident : $idx:tt),+) => {
+        impl<$($name: FromValue),+> FromValue for ($($name,)+) {
+            fn from_value(value: &Value) -> Result<Self, ConversionError> {
+                let items: Vec<Value> = match value {
+                    Value::Var(_) => return Err(ConversionError::Unresolved),
+                    Value::Sym(s) if crate::value::is_reified_var_sym(s) => {
+                        return Err(ConversionError::Unresolved)
+                    }
+                    _ => proper_list_items(value)?,
+                };
+                let mut it = items.into_iter();
+                Ok(($(
+                    $name::from_value(&it.next().ok_or(ConversionError::TypeMismatch {
+                        expected: "tuple of matching arity",
+                        found: value.clone(),
+                    })?)?,
+                )+))
+            }
+        }
+    };
+}
+
+fn proper_list_items(value: &Value) -> Result<Vec<Value>, ConversionError> {
+    match value {
+        Value::Nil => Ok(Vec::new()),
+        Value::Cons(head, tail) => {
+            let mut items = vec![(**head).clone()];
+            items.extend(proper_list_items(tail)?);
+            Ok(items)
+        }
+        other => Err(ConversionError::TypeMismatch {
+            expected: "proper list",
+            found: other.clone(),
+        }),
+    }
+}
+
+from_value_tuple!(A: 0, B: 1);
+from_value_tuple!(A: 0, B: 1, C: 2);
@@ -0,0 +1,115 @@
+//! The `Goal` trait every relation and combinator returns, plus the
+//! handful of primitive goals (`eq`, `succeed`, `fail`, `once`, `ifte`)
+//! that `disj!`/`conj!`/`conda!`/`condu!` expand into.
+
+use crate::state::StatSubs;
+use crate::stream::{advance, Stream};
+use crate::Value;
+
+/// Something that, applied to a state, produces a stream of states: a
+/// relation call, `eq(u, v)`, or a `disj!`/`conj!` combination of either.
+pub trait Goal<S> {
+    fn apply(&self, s: S) -> Stream;
+}
+
+impl<S, F: Fn(S) -> Stream> Goal<S> for F {
+    fn apply(&self, s: S) -> Stream {
+        self(s)
+    }
+}
+
+/// The goal that unifies `u` and `v`, succeeding with the extended state
+/// if they unify and failing otherwise.
+pub fn eq(u: impl Into<Value>, v: impl Into<Value>) -> impl Goal<StatSubs> + Clone {
+    let u = u.into();
+    let v = v.into();
+    move |s: StatSubs| match s.unify(&u, &v) {
+        Some(s) => Stream::unit(s),
+        None => Stream::mzero(),
+    }
+}
+
+/// The goal that always succeeds, unchanged: `conj!`'s identity for an
+/// empty argument list (`disj!`'s is [`fail`]).
+pub fn succeed() -> impl Goal<StatSubs> + Clone {
+    move |s: StatSubs| Stream::unit(s)
+}
+
+/// The goal that never succeeds.
+pub fn fail() -> impl Goal<StatSubs> + Clone {
+    move |_s: StatSubs| Stream::mzero()
+}
+
+/// Run `g` against `s`, keeping only its first solution if it has one.
+pub fn once(g: impl Goal<StatSubs> + Clone + 'static) -> impl Goal<StatSubs> + Clone {
+    move |s: StatSubs| match advance(g.apply(s)) {
+        Some((state, _rest)) => Stream::unit(state),
+        None => Stream::mzero(),
+    }
+}
+
+/// `conda!`'s binary case: if `cond` has any solution, commit to `then`
+/// run against every one of them; otherwise run `els` against the
+/// original state.
+pub fn ifte(
+    cond: impl Goal<StatSubs> + Clone + 'static,
+    then: impl Goal<StatSubs> + Clone + 'static,
+    els: impl Goal<StatSubs> + Clone + 'static,
+) -> impl Goal<StatSubs> + Clone {
+    move |s: StatSubs| match advance(cond.apply(s.clone())) {
+        Some((state, rest)) => {
+            let then = then.clone();
+            crate::stream::bind(Stream::Cons(Box::new(state), Box::new(rest)), move |s2| {
+                then.apply(s2)
+            })
+        }
+        None => els.apply(s),
+    }
+}
+
+/// An iterator over the trampolined stream of solutions a goal produces
+/// against the empty state: never recurses on the Rust stack no matter
+/// how many solutions are pulled, which is what makes `run!(*, ...)` over
+/// an infinite relation safe to iterate (if not always safe to collect).
+pub struct Solutions {
+    stream: Option<Stream>,
+}
+
+impl Iterator for Solutions {
+    type Item = StatSubs;
+
+    fn next(&mut self) -> Option<StatSubs> {
+        let stream = self.stream.take()?;
+        match advance(stream) {
+            Some((state, rest)) => {
+                self.stream = Some(rest);
+                Some(state)
+            }
+            None => None,
+        }
+    }
+}
+
+/// `run!`'s entry points: running a goal against the empty state, driven
+/// by the `advance` trampoline rather than direct recursion.
+pub trait GoalExt: Goal<StatSubs> + Sized + 'static {
+    /// All solutions, as a (possibly infinite) iterator.
+    fn iter(self) -> Solutions {
+        Solutions {
+            stream: Some(self.apply(StatSubs::new())),
+        }
+    }
+
+    /// All solutions, collected eagerly. Only terminates if `self` has
+    /// finitely many.
+    fn run_inf(self) -> Vec<StatSubs> {
+        self.iter().collect()
+    }
+
+    /// At most `n` solutions.
+    fn run(self, n: usize) -> Vec<StatSubs> {
+        self.iter().take(n).collect()
+    }
+}
+
+impl<G: Goal<StatSubs> + 'static> GoalExt for G {}
@@ -0,0 +1,27 @@
+//! Goal-level binary combinators that `disj!`/`conj!` expand to.
+//!
+//! These used to be implemented directly over `Stream`, but that job now
+//! belongs to `mplus`/`bind` (see `stream.rs`); `disj2`/`conj2` only
+//! apply each goal to the current state and hand the resulting streams
+//! to those primitives.
+
+use crate::state::StatSubs;
+use crate::stream::{bind, mplus};
+use crate::Goal;
+
+/// `disj!`'s binary case: succeeds with everything `a` or `b` succeeds
+/// with, fairly interleaved. `disj!`/`conj!` nest these arbitrarily
+/// deep, so every leaf and every intermediate result has to stay `Clone`
+/// for the whole expression tree to be one.
+pub fn disj2(a: impl Goal<StatSubs> + Clone + 'static, b: impl Goal<StatSubs> + Clone + 'static) -> impl Goal<StatSubs> + Clone {
+    move |s: StatSubs| mplus(a.apply(s.clone()), b.apply(s))
+}
+
+/// `conj!`'s binary case: succeeds with whatever `b` succeeds with,
+/// given every state `a` succeeds with.
+pub fn conj2(a: impl Goal<StatSubs> + Clone + 'static, b: impl Goal<StatSubs> + Clone + 'static) -> impl Goal<StatSubs> + Clone {
+    move |s: StatSubs| {
+        let b = b.clone();
+        bind(a.apply(s), move |s2| b.clone().apply(s2))
+    }
+}
@@ -0,0 +1,20 @@
+//! A small, macro-driven miniKANREN embedding.
+//!
+//! `macros.rs` is the DSL surface (`disj!`, `conj!`, `defrel!`, `run!`,
+//! `fresh!`, `conde!`/`conda!`/`condu!`, `matche!`); everything else here
+//! is the machinery it expands into.
+
+pub mod constraints;
+pub mod convert;
+mod goal;
+mod goal2;
+mod macros;
+mod state;
+mod stream;
+mod value;
+
+pub use goal::{eq, fail, ifte, once, succeed, Goal, GoalExt, Solutions};
+pub use goal2::{conj2, disj2};
+pub use state::StatSubs;
+pub use stream::Stream;
+pub use value::{Value, Var};
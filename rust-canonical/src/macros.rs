@@ -16,13 +16,118 @@ macro_rules! conj {
     ($g0:expr, $($g:expr),*) => { conj2($g0, conj!($($g),*))}
 }
 
+/// Creates a goal that succeeds unless `u` and `v` are equal, now or
+/// later in the search, the way Scheme's `=/=` does. See
+/// [`crate::constraints::neq`].
+#[macro_export]
+macro_rules! neq {
+    ($u:expr, $v:expr) => {
+        $crate::constraints::neq($u, $v)
+    };
+}
+
 /// Define a relation.
 /// A relation is a function that creates a goal.
 #[macro_export]
 macro_rules! defrel {
+    // variadic: a trailing `..rest` parameter collects any number of
+    // further terms into a `Vec<Value>`. Each fixed argument keeps its
+    // own trailing comma in the matcher (rather than a comma *separator*
+    // before `..rest`), so the arm also matches when there are zero
+    // fixed arguments, e.g. `defrel!(pub allo(..rest) { ... })`.
+    ($(#[$outer:meta])* pub $name:ident($($args:ident,)* ..$rest:ident) { $($g:expr),* $(,)? }) => {
+        $(#[$outer])*
+        pub fn $name<RestT: 'static + Into<Value>>($($args: impl 'static + Into<Value>,)* $rest: Vec<RestT>) -> impl Goal<StatSubs> + Clone {
+            $(
+                let $args = $args.into();
+            )*
+            let $rest: Vec<Value> = $rest.into_iter().map(Into::into).collect();
+            move |s| {
+                $(
+                    let $args = $args.clone();
+                )*
+                let $rest = $rest.clone();
+                Stream::suspension(move || conj!($($g),*).apply(s))
+            }
+        }
+    };
+
+    ($(#[$outer:meta])* $name:ident($($args:ident,)* ..$rest:ident) { $($g:expr),* $(,)? }) => {
+        $(#[$outer])*
+        fn $name<RestT: 'static + Into<Value>>($($args: impl 'static + Into<Value>,)* $rest: Vec<RestT>) -> impl Goal<StatSubs> + Clone {
+            $(
+                let $args = $args.into();
+            )*
+            let $rest: Vec<Value> = $rest.into_iter().map(Into::into).collect();
+            move |s| {
+                $(
+                    let $args = $args.clone();
+                )*
+                let $rest = $rest.clone();
+                Stream::suspension(move || conj!($($g),*).apply(s))
+            }
+        }
+    };
+
+    // alternate syntax: separate goals with ;
+    (pub $name:ident($($args:ident,)* ..$rest:ident) { $($g:expr);* $(;)? }) => {
+        defrel!{pub $name($($args,)* ..$rest) { $($g),* }}
+    };
+
+    ($name:ident($($args:ident,)* ..$rest:ident) { $($g:expr);* $(;)? }) => {
+        defrel!{$name($($args,)* ..$rest) { $($g),* }}
+    };
+
+    // A final `[arg = expr]` parameter may be omitted by callers by
+    // passing a typed `None` (e.g. `None::<Value>`; a bare `None` leaves
+    // `LastT` uninferred), who then get `expr` in its place. The brackets are
+    // required: `ident`-typed fragments can't be told apart from the
+    // preceding `$args` repetition by the macro matcher (a bare
+    // trailing `last = default` is ambiguous), so the defaulted
+    // parameter needs its own delimiter to mark where it starts.
+    ($(#[$outer:meta])* pub $name:ident($($args:ident,)* [$last:ident = $default:expr]) { $($g:expr),* $(,)? }) => {
+        $(#[$outer])*
+        pub fn $name<LastT: 'static + Into<Value>>($($args: impl 'static + Into<Value>,)* $last: Option<LastT>) -> impl Goal<StatSubs> + Clone {
+            $(
+                let $args = $args.into();
+            )*
+            let $last: Value = match $last {
+                Some(v) => v.into(),
+                None => ($default).into(),
+            };
+            move |s| {
+                $(
+                    let $args = $args.clone();
+                )*
+                let $last = $last.clone();
+                Stream::suspension(move || conj!($($g),*).apply(s))
+            }
+        }
+    };
+
+    ($(#[$outer:meta])* $name:ident($($args:ident,)* [$last:ident = $default:expr]) { $($g:expr),* $(,)? }) => {
+        $(#[$outer])*
+        fn $name<LastT: 'static + Into<Value>>($($args: impl 'static + Into<Value>,)* $last: Option<LastT>) -> impl Goal<StatSubs> + Clone {
+            $(
+                let $args = $args.into();
+            )*
+            let $last: Value = match $last {
+                Some(v) => v.into(),
+                None => ($default).into(),
+            };
+            move |s| {
+                $(
+                    let $args = $args.clone();
+                )*
+                let $last = $last.clone();
+                Stream::suspension(move || conj!($($g),*).apply(s))
+            }
+        }
+    };
+
     ($(#[$outer:meta])* pub $name:ident($($args:ident),*) { $($g:expr),* $(,)? }) => {
         $(#[$outer])*
-        pub fn $name($($args: impl 'static + Into<Value>),*) -> impl Goal<StatSubs> {
+        pub fn $name($($args: impl 'static + Into<Value>),*) -> impl Goal<StatSubs> + Clone {
             $(
                 let $args = $args.into();
             )*
@@ -37,7 +142,7 @@ macro_rules! defrel {
 
     ($(#[$outer:meta])* $name:ident($($args:ident),*) { $($g:expr),* $(,)? }) => {
         $(#[$outer])*
-        fn $name($($args: impl 'static + Into<Value>),*) -> impl Goal<StatSubs> {
+        fn $name($($args: impl 'static + Into<Value>),*) -> impl Goal<StatSubs> + Clone {
             $(
                 let $args = $args.into();
             )*
@@ -111,7 +216,7 @@ macro_rules! run {
     (@ *, $q:ident, $($g:expr),* $(,)?) => {{
         let $q = Var::new(stringify!($q));
         let var = Value::var($q.clone());
-        conj!($($g),*).run_inf().map(move |s| s.reify(&var))
+        conj!($($g),*).run_inf().into_iter().map(move |s| s.reify(&var)).collect::<Vec<_>>()
     }};
 
     (@ iter, $q:ident, $($g:expr),* $(,)?) => {{
@@ -123,7 +228,7 @@ macro_rules! run {
     (@ $n:expr, $q:ident, $($g:expr),* $(,)?) => {{
         let $q = Var::new(stringify!($q));
         let var = Value::var($q.clone());
-        conj!($($g),*).run($n).map(move |s| s.reify(&var))
+        conj!($($g),*).run($n).into_iter().map(move |s| s.reify(&var)).collect::<Vec<_>>()
     }};
 }
 
@@ -170,3 +275,102 @@ macro_rules! condu {
         conda!($(once($gO), $($g),*);*)
     }
 }
+
+/// Pattern-match `term` against cons-list patterns, the way The Reasoned
+/// Schemer's `matche` does.
+///
+/// ```ignore
+/// matche!(term,
+///     [(), g1, g2];
+///     [(head . tail), g3];
+///     [x, g4]
+/// )
+/// ```
+/// Each line is a pattern followed by the goals that run when `term`
+/// unifies with it. `()` matches the empty list, `(a . d)` matches a
+/// cons, and a bare identifier introduces a fresh variable scoped to
+/// that line; every line is expanded into `fresh!` + `eq` + `conj!` and
+/// the lines themselves are combined with `disj!`.
+#[macro_export]
+macro_rules! matche {
+    ($term:expr, $([$pat:tt, $($g:expr),* $(,)?]);* $(;)?) => {
+        disj!($(
+            matche_line!(@ () $pat ; $term, $pat, $($g),*)
+        );*)
+    };
+}
+
+/// Implementation detail of `matche!`: walks a single pattern, collecting
+/// every identifier it introduces as a fresh variable, then wraps the
+/// line's goals in `fresh!` with exactly those variables in scope.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! matche_line {
+    (@ ($($acc:ident),*) ; $term:expr, $pat:tt, $($g:expr),*) => {
+        fresh!(($($acc),*), conj!(eq(matche_pat!($pat), $term.clone()), $($g),*))
+    };
+
+    // `()` introduces no variables.
+    (@ ($($acc:ident),*) () $($rest:tt)* ; $term:expr, $pat:tt, $($g:expr),*) => {
+        matche_line!(@ ($($acc),*) $($rest)* ; $term, $pat, $($g),*)
+    };
+
+    // A dotted pair pushes both of its sub-patterns onto the work list.
+    (@ ($($acc:ident),*) ($a:tt . $d:tt) $($rest:tt)* ; $term:expr, $pat:tt, $($g:expr),*) => {
+        matche_line!(@ ($($acc),*) $a $d $($rest)* ; $term, $pat, $($g),*)
+    };
+
+    // A literal introduces no variables.
+    (@ ($($acc:ident),*) $lit:literal $($rest:tt)* ; $term:expr, $pat:tt, $($g:expr),*) => {
+        matche_line!(@ ($($acc),*) $($rest)* ; $term, $pat, $($g),*)
+    };
+
+    // Anything else still unconsumed is an identifier: a fresh variable.
+    (@ () $x:ident $($rest:tt)* ; $term:expr, $pat:tt, $($g:expr),*) => {
+        matche_line!(@ ($x) $($rest)* ; $term, $pat, $($g),*)
+    };
+    (@ ($($acc:ident),+) $x:ident $($rest:tt)* ; $term:expr, $pat:tt, $($g:expr),*) => {
+        matche_line!(@ ($($acc),+ , $x) $($rest)* ; $term, $pat, $($g),*)
+    };
+}
+
+/// Implementation detail of `matche!`: compiles a single pattern into the
+/// `Value` it denotes, reusing the crate's existing cons/nil constructors.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! matche_pat {
+    (()) => { Value::nil() };
+    (($a:tt . $d:tt)) => { Value::cons(matche_pat!($a), matche_pat!($d)) };
+    ($lit:literal) => { Value::from($lit) };
+    ($x:ident) => { Value::var($x.clone()) };
+}
+
+/// Convert one reified `Value` into a concrete Rust type, the way clap's
+/// `value_t!` converts one parsed argument.
+///
+/// Returns `Result<T, ConversionError>`: `Err(ConversionError::Unresolved)`
+/// if `solution` is still a fresh variable, `Err(ConversionError::TypeMismatch { .. })`
+/// if its shape doesn't match `T`.
+#[macro_export]
+macro_rules! value_of {
+    ($solution:expr, $ty:ty) => {
+        <$ty as $crate::convert::FromValue>::from_value(&$solution)
+    };
+}
+
+/// Like `run!`, but maps every solution through `value_of!`: every form
+/// (`n`, `*` or the bare iterator form) returns an iterator that yields
+/// `Result<T, ConversionError>` instead of the raw `Value`. The target type comes first, set off by `;`, rather than
+/// trailing the goal list: a trailing `$ty:ty` can't be told apart from
+/// one more repetition of `$g:expr` by the macro matcher, so there's no
+/// unambiguous way to let it come last.
+///
+/// ```ignore
+/// let xs: Result<Vec<i64>, _> = run_typed!(i64; 5, q, member(q, list)).into_iter().collect();
+/// ```
+#[macro_export]
+macro_rules! run_typed {
+    ($ty:ty; $($rest:tt)*) => {
+        run!($($rest)*).into_iter().map(|v| $crate::value_of!(v, $ty))
+    };
+}
@@ -0,0 +1,188 @@
+//! The substitution every goal threads through: a `Var -> Value` map plus
+//! whatever constraints (`=/=`, `symbolo`/`numbero`, `absento`) have been
+//! registered against the variables it hasn't bound yet.
+
+use crate::constraints::{self, Disequality, TypeConstraint};
+use crate::value::{Value, Var};
+use std::collections::HashMap;
+
+/// The state a goal is applied to and returns: a substitution together
+/// with its still-live constraints. Cloning copies every binding and
+/// constraint: the substitution is a plain `HashMap`, with no
+/// structural sharing.
+#[derive(Debug, Clone, Default)]
+pub struct StatSubs {
+    subst: HashMap<Var, Value>,
+    constraints: Vec<Disequality>,
+    type_constraints: Vec<(Var, TypeConstraint)>,
+    absento: Vec<(Value, Value)>,
+}
+
+impl StatSubs {
+    /// The empty state: no bindings, no constraints.
+    pub fn new() -> StatSubs {
+        StatSubs::default()
+    }
+
+    /// Follow `v` through the substitution until it is unbound or not a
+    /// variable. Does not recurse into `Cons` structure; see
+    /// [`Self::reify`] for that.
+    pub fn walk(&self, v: &Value) -> Value {
+        match v {
+            Value::Var(var) => match self.subst.get(var) {
+                Some(found) => self.walk(found),
+                None => v.clone(),
+            },
+            other => other.clone(),
+        }
+    }
+
+    fn walk_deep(&self, v: &Value) -> Value {
+        match self.walk(v) {
+            Value::Cons(h, t) => Value::cons(self.walk_deep(&h), self.walk_deep(&t)),
+            other => other,
+        }
+    }
+
+    /// Walk `v` against `self.subst`, overridden by any binding already
+    /// recorded in `added` — the not-yet-committed bindings a unification
+    /// in progress has taken so far.
+    fn walk_tentative(&self, v: &Value, added: &[(Var, Value)]) -> Value {
+        let mut current = v.clone();
+        loop {
+            match &current {
+                Value::Var(var) => {
+                    let found = added
+                        .iter()
+                        .rev()
+                        .find(|(a, _)| a == var)
+                        .map(|(_, val)| val.clone())
+                        .or_else(|| self.subst.get(var).cloned());
+                    match found {
+                        Some(next) => current = next,
+                        None => return current,
+                    }
+                }
+                _ => return current,
+            }
+        }
+    }
+
+    /// Unify `u` and `v` against this substitution, tracking the new
+    /// var/value bindings it would take along the way, without
+    /// committing to any of them.
+    fn raw_unify(&self, u: &Value, v: &Value, added: &mut Vec<(Var, Value)>) -> Option<()> {
+        let u = self.walk_tentative(u, added);
+        let v = self.walk_tentative(v, added);
+        match (u, v) {
+            (Value::Var(a), Value::Var(b)) if a == b => Some(()),
+            (Value::Var(a), v) => {
+                added.push((a, v));
+                Some(())
+            }
+            (u, Value::Var(b)) => {
+                added.push((b, u));
+                Some(())
+            }
+            (Value::Cons(h1, t1), Value::Cons(h2, t2)) => {
+                self.raw_unify(&h1, &h2, added)?;
+                self.raw_unify(&t1, &t2, added)
+            }
+            (u, v) if u == v => Some(()),
+            _ => None,
+        }
+    }
+
+    /// Unify `u` and `v`, extending and constraint-checking the
+    /// substitution with every new binding unification takes along the
+    /// way. `None` if the terms can't be unified, or if unifying them
+    /// would violate a stored constraint.
+    pub fn unify(self, u: &Value, v: &Value) -> Option<StatSubs> {
+        let mut added = Vec::new();
+        self.raw_unify(u, v, &mut added)?;
+        if added.is_empty() {
+            return Some(self);
+        }
+        let mut next = self;
+        for (var, val) in added {
+            next.subst.insert(var, val);
+        }
+        constraints::extend_checked(next)
+    }
+
+    /// Unify `u` and `v` *without* extending `self`: returns the list of
+    /// new bindings unification would have taken, which is exactly the
+    /// "prefix" `neq`/constraint re-checking needs to inspect a
+    /// hypothetical unification rather than commit to it.
+    pub(crate) fn unify_prefix(&self, u: &Value, v: &Value) -> Option<Vec<(Value, Value)>> {
+        let mut added = Vec::new();
+        self.raw_unify(u, v, &mut added)?;
+        Some(added.into_iter().map(|(var, val)| (Value::Var(var), val)).collect())
+    }
+
+    pub(crate) fn add_constraint(mut self, c: Disequality) -> StatSubs {
+        self.constraints.push(c);
+        self
+    }
+
+    pub(crate) fn constraints(&self) -> Vec<Disequality> {
+        self.constraints.clone()
+    }
+
+    pub(crate) fn clear_constraints(mut self) -> StatSubs {
+        self.constraints.clear();
+        self
+    }
+
+    pub(crate) fn add_type_constraint(mut self, v: Var, ty: TypeConstraint) -> StatSubs {
+        self.type_constraints.push((v, ty));
+        self
+    }
+
+    pub(crate) fn type_constraints(&self) -> Vec<(Var, TypeConstraint)> {
+        self.type_constraints.clone()
+    }
+
+    pub(crate) fn clear_type_constraints(mut self) -> StatSubs {
+        self.type_constraints.clear();
+        self
+    }
+
+    pub(crate) fn add_absento(mut self, a: Value, t: Value) -> StatSubs {
+        self.absento.push((a, t));
+        self
+    }
+
+    pub(crate) fn absento_constraints(&self) -> Vec<(Value, Value)> {
+        self.absento.clone()
+    }
+
+    /// Walk `var` all the way down, renaming every variable still fresh
+    /// in first-appearance order (`_0`, `_1`, ...) the way Scheme
+    /// miniKanren's `reify` does, then, when `var` is itself still fresh,
+    /// append its residual disequality constraints so the printed
+    /// solution stays meaningful.
+    pub fn reify(&self, var: &Value) -> Value {
+        let walked = self.walk_deep(var);
+        let mut seen = HashMap::new();
+        let renamed = rename_fresh_vars(&walked, &mut seen);
+        match &renamed {
+            Value::Sym(placeholder) if crate::value::is_reified_var_sym(placeholder) => {
+                Value::Sym(constraints::format_with_constraints(self, var, &renamed))
+            }
+            _ => renamed,
+        }
+    }
+}
+
+fn rename_fresh_vars(v: &Value, seen: &mut HashMap<Var, usize>) -> Value {
+    match v {
+        Value::Var(var) => {
+            let next_idx = seen.len();
+            let idx = *seen.entry(var.clone()).or_insert(next_idx);
+            Value::Sym(format!("_{idx}"))
+        }
+        Value::Cons(h, t) => Value::cons(rename_fresh_vars(h, seen), rename_fresh_vars(t, seen)),
+        other => other.clone(),
+    }
+}
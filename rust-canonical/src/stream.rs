@@ -0,0 +1,100 @@
+//! A fair, trampolined search stream.
+//!
+//! `run!(*, ...)` over an infinite relation used to recurse once per
+//! produced state, which eventually blew the Rust stack. `Stream` now
+//! exposes a single-step `pull`, so `mplus`/`bind` never recurse
+//! directly into each other — they return a suspended `Stream` and the
+//! caller (ultimately the trampoline loop in `run`/`iter`/`run_inf`)
+//! decides when to take the next step.
+//!
+//! `mplus`/`bind` operate on `Stream`s; they are the engine behind the
+//! goal-level `disj2`/`conj2` that `disj!`/`conj!` expand to (see
+//! `goal2.rs`), which operate on `Goal`s and only reach into a `Stream`
+//! once each goal has been applied to a state.
+
+use crate::state::StatSubs;
+
+/// A lazily-unfolding, interleaved stream of states.
+pub enum Stream {
+    Empty,
+    /// A single step still to be taken, e.g. the body of a relation call.
+    Suspension(Box<dyn FnOnce() -> Stream>),
+    /// One state ready now, with the rest of the stream behind it.
+    Cons(Box<StatSubs>, Box<Stream>),
+}
+
+impl Stream {
+    /// The empty stream: no solutions.
+    pub fn mzero() -> Stream {
+        Stream::Empty
+    }
+
+    /// A stream of exactly one solution.
+    pub fn unit(s: StatSubs) -> Stream {
+        Stream::Cons(Box::new(s), Box::new(Stream::Empty))
+    }
+
+    /// Suspend a computation so it is only stepped on demand, which is
+    /// what makes `defrel!`-defined relations safe to call recursively.
+    pub fn suspension(f: impl FnOnce() -> Stream + 'static) -> Stream {
+        Stream::Suspension(Box::new(f))
+    }
+
+    /// Advance the stream by exactly one step: a mature state is handed
+    /// back together with the rest of the stream; a suspension is
+    /// forced exactly once and its result returned as the "not yet
+    /// mature" continuation. Forcing happens here and nowhere else, so a
+    /// recursive relation call only ever grows the heap, never the Rust
+    /// call stack.
+    pub fn pull(self) -> (Option<StatSubs>, Stream) {
+        match self {
+            Stream::Empty => (None, Stream::Empty),
+            Stream::Suspension(f) => (None, f()),
+            Stream::Cons(s, rest) => (Some(*s), *rest),
+        }
+    }
+}
+
+/// Fair disjunction over two streams. Pulls one step from `a`; if it
+/// produced a state, that state is emitted and search continues with `b`
+/// swapped to the front. If `a` only advanced, the swap still happens,
+/// which is what keeps either branch from starving the other on an
+/// infinite relation.
+pub fn mplus(a: Stream, b: Stream) -> Stream {
+    Stream::suspension(move || match a.pull() {
+        (Some(s), a_rest) => Stream::Cons(Box::new(s), Box::new(mplus(b, a_rest))),
+        // `a` is permanently exhausted, not merely suspended: swapping in
+        // a fresh suspension here would just recreate this exact case
+        // forever once `b` is exhausted too, so hand back `b` directly.
+        (None, Stream::Empty) => b,
+        (None, a_rest) => Stream::suspension(move || mplus(b, a_rest)),
+    })
+}
+
+/// Conjunction over a stream and a state-to-stream continuation. Pulls
+/// one step from `a`; each state it produces is fed into `b`, and the
+/// resulting substreams are combined with the same interleaving `mplus`
+/// so a goal that itself diverges on its first solution doesn't starve
+/// the rest of `a`.
+pub fn bind(a: Stream, b: impl Fn(StatSubs) -> Stream + Clone + 'static) -> Stream {
+    Stream::suspension(move || match a.pull() {
+        (Some(s), a_rest) => mplus(b(s), bind(a_rest, b)),
+        // Same reasoning as `mplus`: once `a` is permanently exhausted,
+        // re-suspending would loop forever instead of ever reaching `Empty`.
+        (None, Stream::Empty) => Stream::Empty,
+        (None, a_rest) => Stream::suspension(move || bind(a_rest, b)),
+    })
+}
+
+/// Trampoline over `pull`, driving the stream forward without recursing
+/// on the Rust stack. Used by `run`/`iter`/`run_inf` to pull successive
+/// solutions out of a (possibly infinite) search.
+pub(crate) fn advance(mut s: Stream) -> Option<(StatSubs, Stream)> {
+    loop {
+        match s.pull() {
+            (Some(state), rest) => return Some((state, rest)),
+            (None, Stream::Empty) => return None,
+            (None, next) => s = next,
+        }
+    }
+}
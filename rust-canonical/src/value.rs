@@ -0,0 +1,146 @@
+//! The dynamically-typed term every relation unifies over.
+
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// A logic variable. Compared and hashed by identity, not by `name` —
+/// `name` is kept only so `fresh!`/`run!` can give variables readable
+/// names in diagnostics.
+#[derive(Debug, Clone)]
+pub struct Var {
+    id: u64,
+    name: &'static str,
+}
+
+impl Var {
+    /// A fresh variable that has never been seen before, named `name`
+    /// for diagnostics (`fresh!`/`run!` pass `stringify!` of the
+    /// variable they bind).
+    pub fn new(name: &'static str) -> Var {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        Var {
+            id: NEXT.fetch_add(1, Ordering::Relaxed),
+            name,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl PartialEq for Var {
+    fn eq(&self, other: &Var) -> bool {
+        self.id == other.id
+    }
+}
+impl Eq for Var {}
+
+impl std::hash::Hash for Var {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl fmt::Display for Var {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}_{}", self.name, self.id)
+    }
+}
+
+/// A cons-list term: a logic variable, a scalar, or a `Cons`/`Nil` pair,
+/// the way Scheme data is built.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Var(Var),
+    Num(i64),
+    Sym(String),
+    Bool(bool),
+    Nil,
+    Cons(Box<Value>, Box<Value>),
+}
+
+impl Value {
+    pub fn var(v: Var) -> Value {
+        Value::Var(v)
+    }
+
+    pub fn nil() -> Value {
+        Value::Nil
+    }
+
+    pub fn cons(head: impl Into<Value>, tail: impl Into<Value>) -> Value {
+        Value::Cons(Box::new(head.into()), Box::new(tail.into()))
+    }
+}
+
+impl From<i64> for Value {
+    fn from(n: i64) -> Value {
+        Value::Num(n)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Value {
+        Value::Sym(s.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Value {
+        Value::Sym(s)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Value {
+        Value::Bool(b)
+    }
+}
+
+impl From<Var> for Value {
+    fn from(v: Var) -> Value {
+        Value::Var(v)
+    }
+}
+
+impl From<()> for Value {
+    fn from(_: ()) -> Value {
+        Value::Nil
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(items: Vec<T>) -> Value {
+        items
+            .into_iter()
+            .rev()
+            .fold(Value::Nil, |tail, head| Value::cons(head.into(), tail))
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Var(v) => write!(f, "{v}"),
+            Value::Num(n) => write!(f, "{n}"),
+            Value::Sym(s) => write!(f, "{s}"),
+            Value::Bool(b) => write!(f, "{b}"),
+            Value::Nil => write!(f, "()"),
+            Value::Cons(h, t) => write!(f, "({h} . {t})"),
+        }
+    }
+}
+
+/// Whether `s` is the placeholder `reify` renders a still-fresh variable
+/// as (`_0`, `_1`, ...), as opposed to a symbol a relation actually
+/// produced. Shared with `convert.rs`, which must treat such a
+/// placeholder as [`crate::convert::ConversionError::Unresolved`] rather
+/// than a real string.
+pub(crate) fn is_reified_var_sym(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars.next() == Some('_') && chars.next().map_or(false, |c| c.is_ascii_digit()) && chars.as_str().chars().all(|c| c.is_ascii_digit()) || {
+        let mut c = s.chars();
+        c.next() == Some('_') && s.len() > 1 && s[1..].chars().all(|c| c.is_ascii_digit())
+    }
+}